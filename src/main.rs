@@ -1,11 +1,16 @@
-use eframe::egui::{
-    self, ColorImage, Slider, TextureOptions, Sense, PointerButton,
-};
+use arboard::{Clipboard, ImageData};
+use eframe::egui::{self, Sense, PointerButton, Slider};
 use eframe::CreationContext;
 use eframe::{egui::CentralPanel, App, Frame, NativeOptions};
+use harfbuzz_rs::{shape, Face as HbFace, Font as HbFont, UnicodeBuffer};
 use image::{ImageBuffer, Rgba, RgbaImage};
-use imageproc::drawing::{draw_hollow_circle_mut, draw_line_segment_mut};
-use rusttype::{Font, Scale, point, PositionedGlyph};
+use lyon_tessellation::{
+    math::point, math::Point, path::Path as LyonPath, path::Winding, BuffersBuilder, FillOptions,
+    FillTessellator, FillVertex, LineCap, StrokeOptions, StrokeTessellator, StrokeVertex,
+    VertexBuffers,
+};
+use rfd::FileDialog;
+use rusttype::{point as rt_point, Font, GlyphId, PositionedGlyph, Scale};
 use std::f64::consts::PI;
 
 /// ---------------------------------------------
@@ -38,50 +43,52 @@ fn generate_polygon_points(
     points
 }
 
-/// ---------------------------------------------
-/// RustType を使って文字を描画する関数
-/// ---------------------------------------------
-fn draw_text(
-    img: &mut RgbaImage,
-    text: &str,
-    x: i32,
-    y: i32,
-    scale: Scale,
-    font: &Font,
-    color: [u8; 4],
-) {
-    let v_metrics = font.v_metrics(scale);
-    let glyphs: Vec<PositionedGlyph> = font
-        .layout(text, scale, point(0.0, v_metrics.ascent))
-        .collect();
-
-    for glyph in glyphs {
-        if let Some(bb) = glyph.pixel_bounding_box() {
-            glyph.draw(|gx, gy, gv| {
-                let px = x + bb.min.x + gx as i32;
-                let py = y + bb.min.y + gy as i32;
-
-                if px >= 0 && px < img.width() as i32 && py >= 0 && py < img.height() as i32 {
-                    let dst = img.get_pixel_mut(px as u32, py as u32);
-                    let alpha = (gv * 255.0) as u8;
-                    let inv_alpha = 255 - alpha;
-
-                    let dst_rgba = dst.0;
-                    let src_rgba = color;
+/// 辺・外接円に適用できる線種。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineStyle {
+    Solid,
+    Dash,
+    SquareDot,
+    RoundDot,
+}
 
-                    dst.0[0] = ((src_rgba[0] as u16 * alpha as u16
-                              + dst_rgba[0] as u16 * inv_alpha as u16) / 255) as u8;
-                    dst.0[1] = ((src_rgba[1] as u16 * alpha as u16
-                              + dst_rgba[1] as u16 * inv_alpha as u16) / 255) as u8;
-                    dst.0[2] = ((src_rgba[2] as u16 * alpha as u16
-                              + dst_rgba[2] as u16 * inv_alpha as u16) / 255) as u8;
-                    dst.0[3] = 255;
-                }
-            });
+impl LineStyle {
+    fn label(self) -> &'static str {
+        match self {
+            LineStyle::Solid => "Solid",
+            LineStyle::Dash => "Dash",
+            LineStyle::SquareDot => "Square Dot",
+            LineStyle::RoundDot => "Round Dot",
         }
     }
 }
 
+/// `style` で描く「描画区間 / 空白区間」の長さを太さから決める。
+/// `stroke_path_styled` (lyon でのテッセレーション) と `svg_stroke_style`
+/// (SVG の `stroke-dasharray`) の両方から参照される共通の間隔定義。
+fn dash_span_lengths(style: LineStyle, thickness: f32) -> (f32, f32) {
+    match style {
+        LineStyle::Solid => (0.0, 0.0),
+        LineStyle::Dash => (thickness * 3.0, thickness * 2.0),
+        LineStyle::SquareDot | LineStyle::RoundDot => (thickness, thickness * 1.8),
+    }
+}
+
+/// 名前付きプリセット。`n_sides` / `diameter` / `offset_deg` への
+/// ユーザー定義プリセットを増やしたい場合はここに追加するだけでよい。
+struct PolygonPreset {
+    name: &'static str,
+    n_sides: usize,
+    diameter: f64,
+    offset_deg: f64,
+}
+
+const PRESETS: &[PolygonPreset] = &[
+    PolygonPreset { name: "Octagon axis-aligned", n_sides: 8, diameter: 700.0, offset_deg: 22.5 },
+    PolygonPreset { name: "Hexagon flat-top", n_sides: 6, diameter: 700.0, offset_deg: 0.0 },
+    PolygonPreset { name: "Pentagon point-up", n_sides: 5, diameter: 700.0, offset_deg: 18.0 },
+];
+
 /// ---------------------------------------------
 /// eguiアプリ用の構造体
 /// ---------------------------------------------
@@ -90,8 +97,23 @@ struct PolygonApp {
     diameter: f64,
     offset_deg: f64,
 
-    zoom: f32,                          // 画像のズーム比率
-    image_texture: Option<egui::TextureHandle>,
+    selected_preset: Option<usize>, // プルダウンで選択中のプリセット(手動変更で None に戻る)
+    preset_list_open: bool,         // プリセットの折りたたみ可能な行リストを開いているか
+
+    edge_style: LineStyle,
+    edge_thickness: f32,
+    circle_style: LineStyle,
+    circle_thickness: f32,
+
+    use_harfbuzz_shaping: bool, // HarfBuzz でカーニング/複雑スクリプトの整形を行うか
+    rotate_labels: bool,        // ラベルを頂点の外向き放射方向に沿わせるか
+
+    zoom: f32, // 表示のズーム比率
+    mesh: Option<egui::epaint::Mesh>, // 多角形中心を原点とするテッセレーション済みメッシュ
+    labels: Vec<(f64, f64, f64)>, // mesh と同じ Generate 時点の頂点 (x, y, 角度)。オンスクリーンラベル描画用
+
+    drag_start: Option<egui::Pos2>,         // ラバーバンド選択のドラッグ開始位置
+    pending_scroll_to: Option<egui::Vec2>,  // 次フレームで適用するスクロールオフセット
 }
 
 impl PolygonApp {
@@ -103,8 +125,394 @@ impl PolygonApp {
             n_sides: 8,
             diameter: 700.0,   // 半径350
             offset_deg: 22.5,  // 4辺が軸と平行になる回転
+            selected_preset: Some(0), // デフォルト値は "Octagon axis-aligned" と一致
+            preset_list_open: false,
+            edge_style: LineStyle::Solid,
+            edge_thickness: 1.0,
+            circle_style: LineStyle::Solid,
+            circle_thickness: 1.0,
+            use_harfbuzz_shaping: true,
+            rotate_labels: false,
             zoom: 1.0,
-            image_texture: None,
+            mesh: None,
+            labels: Vec::new(),
+            drag_start: None,
+            pending_scroll_to: None,
+        }
+    }
+}
+
+/// ---------------------------------------------
+/// ラバーバンド選択の破線オーバーレイ
+/// ---------------------------------------------
+fn dashed_line_segment(painter: &egui::Painter, from: egui::Pos2, to: egui::Pos2, color: egui::Color32) {
+    let dash_len = 6.0;
+    let gap_len = 4.0;
+    let dir = to - from;
+    let len = dir.length();
+    if len < f32::EPSILON {
+        return;
+    }
+    let unit = dir / len;
+
+    let mut travelled = 0.0;
+    while travelled < len {
+        let seg_len = dash_len.min(len - travelled);
+        let seg_from = from + unit * travelled;
+        let seg_to = from + unit * (travelled + seg_len);
+        painter.line_segment([seg_from, seg_to], (1.5, color));
+        travelled += dash_len + gap_len;
+    }
+}
+
+/// ドラッグ中の選択矩形を、起点から外向きにダッシュが伸びるように描画する。
+/// カーソルが起点より左/右・上/下どちらにあるかで各辺の描画方向を反転させる。
+fn draw_drag_rect_overlay(painter: &egui::Painter, origin: egui::Pos2, current: egui::Pos2) {
+    let rect = egui::Rect::from_two_pos(origin, current);
+    let color = egui::Color32::from_rgb(30, 144, 255);
+
+    let grows_right = current.x >= origin.x;
+    let grows_down = current.y >= origin.y;
+
+    let top_left = rect.left_top();
+    let top_right = rect.right_top();
+    let bottom_left = rect.left_bottom();
+    let bottom_right = rect.right_bottom();
+
+    let (top_from, top_to) = if grows_right { (top_left, top_right) } else { (top_right, top_left) };
+    let (bottom_from, bottom_to) = if grows_right { (bottom_left, bottom_right) } else { (bottom_right, bottom_left) };
+    let (left_from, left_to) = if grows_down { (top_left, bottom_left) } else { (bottom_left, top_left) };
+    let (right_from, right_to) = if grows_down { (top_right, bottom_right) } else { (bottom_right, top_right) };
+
+    dashed_line_segment(painter, top_from, top_to, color);
+    dashed_line_segment(painter, bottom_from, bottom_to, color);
+    dashed_line_segment(painter, left_from, left_to, color);
+    dashed_line_segment(painter, right_from, right_to, color);
+}
+
+fn mesh_vertex(p: Point, color: egui::Color32) -> egui::epaint::Vertex {
+    egui::epaint::Vertex {
+        pos: egui::pos2(p.x, p.y),
+        uv: egui::epaint::WHITE_UV,
+        color,
+    }
+}
+
+/// 単一セグメントを、角の丸いカプセル(太さ `thickness` で掃引した円)として塗りつぶす。
+fn add_capsule_segment(
+    geometry: &mut VertexBuffers<egui::epaint::Vertex, u32>,
+    from: Point,
+    to: Point,
+    thickness: f32,
+    color: egui::Color32,
+    tolerance: f32,
+) {
+    let mut builder = LyonPath::builder();
+    builder.begin(from);
+    builder.line_to(to);
+    builder.end(false);
+    let path = builder.build();
+
+    let options = StrokeOptions::default()
+        .with_line_width(thickness)
+        .with_line_cap(LineCap::Round)
+        .with_tolerance(tolerance);
+
+    if let Err(err) = StrokeTessellator::new().tessellate_path(
+        &path,
+        &options,
+        &mut BuffersBuilder::new(geometry, |v: StrokeVertex| mesh_vertex(v.position(), color)),
+    ) {
+        eprintln!("Failed to tessellate dash segment: {err}");
+    }
+}
+
+/// 塗りつぶした円のドットを 1 つ追加する。
+fn add_round_dot(
+    geometry: &mut VertexBuffers<egui::epaint::Vertex, u32>,
+    center: Point,
+    radius: f32,
+    color: egui::Color32,
+    tolerance: f32,
+) {
+    let mut builder = LyonPath::builder();
+    builder.add_circle(center, radius, Winding::Positive);
+    let path = builder.build();
+
+    let options = FillOptions::default().with_tolerance(tolerance);
+    if let Err(err) = FillTessellator::new().tessellate_path(
+        &path,
+        &options,
+        &mut BuffersBuilder::new(geometry, |v: FillVertex| mesh_vertex(v.position(), color)),
+    ) {
+        eprintln!("Failed to tessellate round dot: {err}");
+    }
+}
+
+/// 塗りつぶした正方形のドットを 1 つ追加する。
+fn add_square_dot(
+    geometry: &mut VertexBuffers<egui::epaint::Vertex, u32>,
+    center: Point,
+    side: f32,
+    color: egui::Color32,
+) {
+    let half = side / 2.0;
+    let mut builder = LyonPath::builder();
+    builder.begin(point(center.x - half, center.y - half));
+    builder.line_to(point(center.x + half, center.y - half));
+    builder.line_to(point(center.x + half, center.y + half));
+    builder.line_to(point(center.x - half, center.y + half));
+    builder.close();
+    let path = builder.build();
+
+    let options = FillOptions::default();
+    if let Err(err) = FillTessellator::new().tessellate_path(
+        &path,
+        &options,
+        &mut BuffersBuilder::new(geometry, |v: FillVertex| mesh_vertex(v.position(), color)),
+    ) {
+        eprintln!("Failed to tessellate square dot: {err}");
+    }
+}
+
+/// 点列 (閉じたループなら `closed = true`) を `style` で指定した線種・太さで描画する。
+/// 破線・点線は各セグメントを弧長に沿って歩き、描画区間/空白区間を交互に積んでいく。
+/// ドット/ダッシュの個数は `MAX_DASH_SPANS` で上限を設け、大直径 + 極小太さの
+/// 組み合わせでテッセレーション呼び出しが際限なく増えるのを防ぐ。
+fn stroke_path_styled(
+    geometry: &mut VertexBuffers<egui::epaint::Vertex, u32>,
+    points: &[Point],
+    closed: bool,
+    style: LineStyle,
+    thickness: f32,
+    color: egui::Color32,
+    tolerance: f32,
+) {
+    if points.len() < 2 {
+        return;
+    }
+
+    if style == LineStyle::Solid {
+        let mut builder = LyonPath::builder();
+        builder.begin(points[0]);
+        for &p in &points[1..] {
+            builder.line_to(p);
+        }
+        if closed {
+            builder.close();
+        } else {
+            builder.end(false);
+        }
+        let path = builder.build();
+
+        let options = StrokeOptions::default()
+            .with_line_width(thickness)
+            .with_line_join(lyon_tessellation::LineJoin::Round)
+            .with_tolerance(tolerance);
+
+        if let Err(err) = StrokeTessellator::new().tessellate_path(
+            &path,
+            &options,
+            &mut BuffersBuilder::new(geometry, |v: StrokeVertex| mesh_vertex(v.position(), color)),
+        ) {
+            eprintln!("Failed to tessellate solid path: {err}");
+        }
+        return;
+    }
+
+    let (mut on_len, mut off_len) = dash_span_lengths(style, thickness);
+
+    let mut loop_points = points.to_vec();
+    if closed {
+        loop_points.push(points[0]);
+    }
+
+    // 大直径 + 極小太さだと on/off 間隔が非常に短くなり、1 回の Generate で
+    // 数万回ものテッセレーション呼び出しが発生しかねない。パス全長から必要な
+    // スパン数を見積もり、上限を超える分は on/off 間隔を比例拡大して間引く。
+    const MAX_DASH_SPANS: f32 = 2000.0;
+    let total_len: f32 = loop_points
+        .windows(2)
+        .map(|seg| (seg[1] - seg[0]).length())
+        .sum();
+    let span_len = on_len + off_len;
+    if span_len > f32::EPSILON {
+        let estimated_spans = total_len / span_len;
+        if estimated_spans > MAX_DASH_SPANS {
+            let shrink = estimated_spans / MAX_DASH_SPANS;
+            on_len *= shrink;
+            off_len *= shrink;
+        }
+    }
+
+    let mut in_pattern = 0.0_f32;
+    let mut drawing = true;
+    for segment in loop_points.windows(2) {
+        let (from, to) = (segment[0], segment[1]);
+        let seg_vec = to - from;
+        let seg_len = seg_vec.length();
+        if seg_len < f32::EPSILON {
+            continue;
+        }
+        let unit = seg_vec / seg_len;
+
+        let mut pos = 0.0_f32;
+        while pos < seg_len {
+            let span_target = if drawing { on_len } else { off_len };
+            let step = (span_target - in_pattern).min(seg_len - pos);
+            let span_from = from + unit * pos;
+            let span_to = from + unit * (pos + step);
+
+            if drawing {
+                match style {
+                    LineStyle::Dash => {
+                        add_capsule_segment(geometry, span_from, span_to, thickness, color, tolerance)
+                    }
+                    LineStyle::RoundDot => {
+                        let mid = span_from + (span_to - span_from) * 0.5;
+                        add_round_dot(geometry, mid, thickness / 2.0, color, tolerance);
+                    }
+                    LineStyle::SquareDot => {
+                        let mid = span_from + (span_to - span_from) * 0.5;
+                        add_square_dot(geometry, mid, thickness, color);
+                    }
+                    LineStyle::Solid => unreachable!(),
+                }
+            }
+
+            pos += step;
+            in_pattern += step;
+            if in_pattern >= span_target - f32::EPSILON {
+                in_pattern = 0.0;
+                drawing = !drawing;
+            }
+        }
+    }
+}
+
+/// ---------------------------------------------
+/// パス・テッセレーション (lyon)
+/// ---------------------------------------------
+/// 外接円・多角形の辺・頂点マーカーを三角形メッシュに変換し、egui の
+/// Painter / GPU でそのまま描画できるようにする。`diameter` px を基準とした
+/// 多角形中心原点のローカル座標系でメッシュを返すので、表示側はズーム量に
+/// 応じて頂点位置をスケール・平行移動するだけでよい。CPU 側の画像バッファは
+/// 一切確保しないため、メモリ使用量は diameter に比例しない。
+/// 辺と外接円にはそれぞれ独立に線種・太さを指定できる。
+#[allow(clippy::too_many_arguments)]
+fn tessellate_polygon_mesh(
+    n_sides: usize,
+    diameter: f64,
+    offset_deg: f64,
+    edge_style: LineStyle,
+    edge_thickness: f32,
+    circle_style: LineStyle,
+    circle_thickness: f32,
+    tolerance: f32,
+) -> egui::epaint::Mesh {
+    let radius = (diameter / 2.0) as f32;
+    let points = generate_polygon_points(n_sides, diameter, offset_deg);
+
+    let mut geometry: VertexBuffers<egui::epaint::Vertex, u32> = VertexBuffers::new();
+
+    // 外接円を円弧に沿ったサンプル点列として近似し、破線/点線を正しく歩けるようにする
+    let circle_segments = 128;
+    let circle_points: Vec<Point> = (0..circle_segments)
+        .map(|i| {
+            let theta = 2.0 * std::f32::consts::PI * (i as f32) / (circle_segments as f32);
+            point(radius * theta.cos(), radius * theta.sin())
+        })
+        .collect();
+    stroke_path_styled(
+        &mut geometry,
+        &circle_points,
+        true,
+        circle_style,
+        circle_thickness,
+        egui::Color32::from_rgb(0, 0, 255),
+        tolerance,
+    );
+
+    // 多角形の辺 (y は画面座標に合わせて反転)
+    let polygon_points: Vec<Point> = points
+        .iter()
+        .map(|&(x, y, _)| point(x as f32, -y as f32))
+        .collect();
+    stroke_path_styled(
+        &mut geometry,
+        &polygon_points,
+        true,
+        edge_style,
+        edge_thickness,
+        egui::Color32::from_rgb(0, 0, 0),
+        tolerance,
+    );
+
+    // 頂点マーカー (塗りつぶし円)
+    for &p in &polygon_points {
+        add_round_dot(&mut geometry, p, 3.0, egui::Color32::from_rgb(255, 0, 0), tolerance);
+    }
+
+    egui::epaint::Mesh {
+        indices: geometry.indices,
+        vertices: geometry.vertices,
+        texture_id: egui::TextureId::default(),
+    }
+}
+
+/// 三角形 `a -> b -> c` の符号付き面積の 2 倍 (エッジ関数)。重心座標による
+/// 内外判定に使う。
+fn edge_fn(a: egui::Pos2, b: egui::Pos2, c: egui::Pos2) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// `tessellate_polygon_mesh` が返す三角形メッシュを、`(offset_x, offset_y)`
+/// だけ平行移動した上でそのまま `img` にラスタライズする。プレビュー(GPU)と
+/// 書き出し(CPU)は同じ `tessellate_polygon_mesh` で頂点データを作るので、
+/// 同じパラメータなら線種・太さが一致する。
+fn rasterize_mesh(img: &mut RgbaImage, mesh: &egui::epaint::Mesh, offset_x: f32, offset_y: f32) {
+    let width = img.width() as i32;
+    let height = img.height() as i32;
+
+    for tri in mesh.indices.chunks(3) {
+        if tri.len() < 3 {
+            continue;
+        }
+        let v0 = mesh.vertices[tri[0] as usize];
+        let v1 = mesh.vertices[tri[1] as usize];
+        let v2 = mesh.vertices[tri[2] as usize];
+
+        let p0 = egui::pos2(v0.pos.x + offset_x, v0.pos.y + offset_y);
+        let p1 = egui::pos2(v1.pos.x + offset_x, v1.pos.y + offset_y);
+        let p2 = egui::pos2(v2.pos.x + offset_x, v2.pos.y + offset_y);
+
+        let area = edge_fn(p0, p1, p2);
+        if area.abs() < f32::EPSILON {
+            continue;
+        }
+
+        let min_x = (p0.x.min(p1.x).min(p2.x).floor() as i32).max(0);
+        let max_x = (p0.x.max(p1.x).max(p2.x).ceil() as i32).min(width - 1);
+        let min_y = (p0.y.min(p1.y).min(p2.y).floor() as i32).max(0);
+        let max_y = (p0.y.max(p1.y).max(p2.y).ceil() as i32).min(height - 1);
+
+        let color = v0.color;
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let p = egui::pos2(x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = edge_fn(p1, p2, p);
+                let w1 = edge_fn(p2, p0, p);
+                let w2 = edge_fn(p0, p1, p);
+                let inside = (w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0)
+                    || (w0 <= 0.0 && w1 <= 0.0 && w2 <= 0.0);
+                if inside {
+                    img.put_pixel(
+                        x as u32,
+                        y as u32,
+                        Rgba([color.r(), color.g(), color.b(), color.a()]),
+                    );
+                }
+            }
         }
     }
 }
@@ -135,139 +543,583 @@ impl App for PolygonApp {
             ui.heading("正多角形プロットツール (Click on image to zoom)");
             ui.separator();
 
+            // プリセットのプルダウン: ヘッダーをクリックすると選択行が展開される
+            let header_label = self
+                .selected_preset
+                .and_then(|i| PRESETS.get(i))
+                .map(|preset| preset.name)
+                .unwrap_or("Custom");
+            if ui.button(format!("Preset: {header_label} ▾")).clicked() {
+                self.preset_list_open = !self.preset_list_open;
+            }
+            if self.preset_list_open {
+                ui.group(|ui| {
+                    for (i, preset) in PRESETS.iter().enumerate() {
+                        let selected = self.selected_preset == Some(i);
+                        if ui.selectable_label(selected, preset.name).clicked() {
+                            self.n_sides = preset.n_sides;
+                            self.diameter = preset.diameter;
+                            self.offset_deg = preset.offset_deg;
+                            self.selected_preset = Some(i);
+                            self.preset_list_open = false;
+                        }
+                    }
+                });
+            }
+
             // 多角形パラメータ
-            ui.add(Slider::new(&mut self.n_sides, 3..=20).text("n_sides (>=3)"));
-            ui.add(Slider::new(&mut self.diameter, 100.0..=5000.0).text("Diameter"));
-            ui.add(Slider::new(&mut self.offset_deg, 0.0..=45.0).text("Offset (deg)"));
+            if ui.add(Slider::new(&mut self.n_sides, 3..=20).text("n_sides (>=3)")).changed() {
+                self.selected_preset = None;
+            }
+            if ui.add(Slider::new(&mut self.diameter, 100.0..=5000.0).text("Diameter")).changed() {
+                self.selected_preset = None;
+            }
+            if ui.add(Slider::new(&mut self.offset_deg, 0.0..=45.0).text("Offset (deg)")).changed() {
+                self.selected_preset = None;
+            }
+
+            // 辺・外接円の線種/太さ (構築用の破線外接円と本体の実線など、独立に指定できる)
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("Edge style")
+                    .selected_text(self.edge_style.label())
+                    .show_ui(ui, |ui| {
+                        for style in [
+                            LineStyle::Solid,
+                            LineStyle::Dash,
+                            LineStyle::SquareDot,
+                            LineStyle::RoundDot,
+                        ] {
+                            ui.selectable_value(&mut self.edge_style, style, style.label());
+                        }
+                    });
+                ui.add(Slider::new(&mut self.edge_thickness, 0.5..=10.0).text("Edge thickness"));
+            });
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("Circle style")
+                    .selected_text(self.circle_style.label())
+                    .show_ui(ui, |ui| {
+                        for style in [
+                            LineStyle::Solid,
+                            LineStyle::Dash,
+                            LineStyle::SquareDot,
+                            LineStyle::RoundDot,
+                        ] {
+                            ui.selectable_value(&mut self.circle_style, style, style.label());
+                        }
+                    });
+                ui.add(Slider::new(&mut self.circle_thickness, 0.5..=10.0).text("Circle thickness"));
+            });
+
+            // 頂点ラベルのオプション (整形は PNG/クリップボード書き出し、回転はそれに加えて
+            // SVG 書き出しで使われる。画面上のラベルには反映されない)
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.use_harfbuzz_shaping, "HarfBuzz label shaping");
+                ui.checkbox(&mut self.rotate_labels, "Rotate labels along edges");
+            });
 
             // ズーム倍率
             ui.label(format!(
-                "Current Zoom: {:.2}x (Left-click=ZoomIn, Right-click=ZoomOut)",
+                "Current Zoom: {:.2}x (Drag a rectangle on the image to zoom to that region)",
                 self.zoom
             ));
 
-            // [Generate] ボタン
-            if ui.button("Generate").clicked() {
-                // diameter の値に応じて画像サイズを動的に決定
-                let rgba = create_image(self.n_sides, self.diameter, self.offset_deg);
+            ui.horizontal(|ui| {
+                // [Generate] ボタン: パラメータからメッシュをテッセレーションし直す
+                if ui.button("Generate").clicked() {
+                    self.mesh = Some(tessellate_polygon_mesh(
+                        self.n_sides,
+                        self.diameter,
+                        self.offset_deg,
+                        self.edge_style,
+                        self.edge_thickness,
+                        self.circle_style,
+                        self.circle_thickness,
+                        0.2,
+                    ));
+                    // メッシュと同じパラメータで頂点ラベルを作り直す (ズレ防止)
+                    self.labels =
+                        generate_polygon_points(self.n_sides, self.diameter, self.offset_deg);
+                }
 
-                // ColorImage に変換
-                let size = [rgba.width() as usize, rgba.height() as usize];
-                let mut rgba_data = Vec::with_capacity(size[0] * size[1] * 4);
-                for (_, _, pixel) in rgba.enumerate_pixels() {
-                    rgba_data.extend_from_slice(&pixel.0);
+                // [Export SVG] ボタン: ラスタ化前の浮動小数点座標をそのまま書き出す
+                if ui.button("Export SVG").clicked() {
+                    if let Some(path) = FileDialog::new()
+                        .set_file_name("polygon.svg")
+                        .add_filter("SVG", &["svg"])
+                        .save_file()
+                    {
+                        let svg = build_polygon_svg(
+                            self.n_sides,
+                            self.diameter,
+                            self.offset_deg,
+                            self.edge_style,
+                            self.edge_thickness,
+                            self.circle_style,
+                            self.circle_thickness,
+                            self.rotate_labels,
+                        );
+                        if let Err(err) = std::fs::write(&path, svg) {
+                            eprintln!("Failed to write SVG to {:?}: {err}", path);
+                        }
+                    }
                 }
 
-                let color_image = ColorImage::from_rgba_unmultiplied(size, &rgba_data);
+                // [Copy to Clipboard] ボタン: 書き出し用ラスタ画像をそのままクリップボードへ
+                if ui.button("Copy to Clipboard").clicked() {
+                    let rgba = render_export_image(
+                        self.n_sides,
+                        self.diameter,
+                        self.offset_deg,
+                        self.edge_style,
+                        self.edge_thickness,
+                        self.circle_style,
+                        self.circle_thickness,
+                        self.use_harfbuzz_shaping,
+                        self.rotate_labels,
+                    );
+                    let (width, height) = (rgba.width() as usize, rgba.height() as usize);
+                    let image_data = ImageData {
+                        width,
+                        height,
+                        bytes: rgba.into_raw().into(),
+                    };
+                    match Clipboard::new() {
+                        Ok(mut clipboard) => {
+                            if let Err(err) = clipboard.set_image(image_data) {
+                                eprintln!("Failed to copy image to clipboard: {err}");
+                            }
+                        }
+                        Err(err) => eprintln!("Failed to access clipboard: {err}"),
+                    }
+                }
 
-                // テクスチャとしてアップロード (最近傍補間)
-                let texture_handle = ctx.load_texture(
-                    "polygon_image",
-                    color_image,
-                    TextureOptions::NEAREST,
-                );
-                self.image_texture = Some(texture_handle);
-            }
+                // [Save PNG...] ボタン: 同じ書き出し用ラスタ画像をファイルへ保存
+                if ui.button("Save PNG...").clicked() {
+                    if let Some(path) = FileDialog::new()
+                        .set_file_name("polygon.png")
+                        .add_filter("PNG", &["png"])
+                        .save_file()
+                    {
+                        let rgba = render_export_image(
+                            self.n_sides,
+                            self.diameter,
+                            self.offset_deg,
+                            self.edge_style,
+                            self.edge_thickness,
+                            self.circle_style,
+                            self.circle_thickness,
+                            self.use_harfbuzz_shaping,
+                            self.rotate_labels,
+                        );
+                        if let Err(err) = rgba.save(&path) {
+                            eprintln!("Failed to save PNG to {:?}: {err}", path);
+                        }
+                    }
+                }
+            });
 
             ui.separator();
 
-            // 生成画像を表示
-            if let Some(img_texture) = &self.image_texture {
-                let size_vec = img_texture.size_vec2();
-                let scaled_size = size_vec * self.zoom;
+            // テッセレーション済みメッシュを Painter で直接描画 (CPU 画像バッファは持たない)
+            if let Some(base_mesh) = &self.mesh {
+                let half_extent = (self.diameter as f32 / 2.0 + 40.0) * self.zoom;
+                let canvas_size = egui::vec2(half_extent * 2.0, half_extent * 2.0);
 
-                egui::ScrollArea::both()
+                let mut scroll_area = egui::ScrollArea::both()
                     .max_width(ui.available_width())
-                    .max_height(ui.available_height())
-                    .show(ui, |ui| {
-                        let image_widget = egui::Image::new((img_texture.id(), scaled_size))
-                            .sense(Sense::click());
-                        let response = ui.add(image_widget);
-
-                        if response.hovered() {
-                            // 左クリック = ズームイン
-                            if response.clicked_by(PointerButton::Primary) {
-                                self.zoom *= 1.1;
-                                self.zoom = self.zoom.clamp(0.1, 10.0);
+                    .max_height(ui.available_height());
+                if let Some(offset) = self.pending_scroll_to.take() {
+                    scroll_area = scroll_area.scroll_offset(offset);
+                }
+
+                scroll_area.show_viewport(ui, |ui, viewport_rect| {
+                    let (canvas_rect, response) =
+                        ui.allocate_exact_size(canvas_size, Sense::drag());
+                    let center = canvas_rect.center();
+
+                    // ズーム量に応じて頂点位置をスケール・平行移動する
+                    let mut mesh = base_mesh.clone();
+                    for vertex in mesh.vertices.iter_mut() {
+                        vertex.pos = center + vertex.pos.to_vec2() * self.zoom;
+                    }
+                    ui.painter().add(egui::Shape::mesh(mesh));
+
+                    // 頂点の座標/角度ラベル。メッシュの頂点と同じズーム/平行移動で重ね描きする
+                    let font_id = egui::FontId::proportional((12.0 * self.zoom).clamp(6.0, 28.0));
+                    for &(x, y, angle_deg) in &self.labels {
+                        let local = egui::vec2(x as f32, -(y as f32));
+                        let pos = center + local * self.zoom;
+                        let label = format!("({:.0}, {:.0}) / {:.1}°", x, y, angle_deg);
+                        ui.painter().text(
+                            pos,
+                            egui::Align2::LEFT_BOTTOM,
+                            label,
+                            font_id.clone(),
+                            egui::Color32::BLACK,
+                        );
+                    }
+
+                    if response.drag_started_by(PointerButton::Primary) {
+                        self.drag_start = response.interact_pointer_pos();
+                    }
+
+                    if let Some(start) = self.drag_start {
+                        if response.dragged_by(PointerButton::Primary) {
+                            if let Some(current) = response.interact_pointer_pos() {
+                                draw_drag_rect_overlay(ui.painter(), start, current);
                             }
-                            // 右クリック = ズームアウト
-                            if response.clicked_by(PointerButton::Secondary) {
-                                self.zoom /= 1.1;
-                                self.zoom = self.zoom.clamp(0.1, 10.0);
+                        }
+
+                        if response.drag_stopped_by(PointerButton::Primary) {
+                            if let Some(end) = response.interact_pointer_pos() {
+                                let drag_rect = egui::Rect::from_two_pos(start, end);
+
+                                // 小さすぎる選択(誤クリック程度)はズーム操作とみなさない
+                                if drag_rect.width() > 4.0 && drag_rect.height() > 4.0 {
+                                    // `ui.available_rect_before_wrap()` would return the
+                                    // ScrollArea content's near-infinite max-rect, not the
+                                    // actually visible window, so use the `viewport_rect`
+                                    // handed back by `show_viewport` instead.
+                                    let scale_x = viewport_rect.width() / drag_rect.width();
+                                    let scale_y = viewport_rect.height() / drag_rect.height();
+                                    let new_zoom =
+                                        (self.zoom * scale_x.min(scale_y)).clamp(0.1, 10.0);
+
+                                    // 選択範囲の中心がビューポート中央に来るようスクロール位置を計算する
+                                    let rel_center =
+                                        (drag_rect.center() - canvas_rect.min) / self.zoom;
+                                    self.zoom = new_zoom;
+                                    self.pending_scroll_to =
+                                        Some(rel_center * self.zoom - viewport_rect.size() / 2.0);
+                                }
                             }
+                            self.drag_start = None;
                         }
-                    });
+                    }
+                });
             }
         });
     }
 }
 
+/// `style`/`thickness` に応じた SVG のストローク属性を返す (`stroke-width` を除く)。
+/// 破線/点線の間隔は `dash_span_lengths` と揃えてあるので、プレビュー/PNG/SVG
+/// のどれを見ても同じ線種に見える。丸ドットは `stroke-linecap="round"` で
+/// `stroke-dasharray` の「描画区間」をほぼ 0 にして点状にし、角ドットは
+/// `stroke-linecap="butt"` で描画区間を太さと同じ長さにして正方形にする。
+fn svg_stroke_style(style: LineStyle, thickness: f64) -> String {
+    let (on_len, off_len) = dash_span_lengths(style, thickness as f32);
+    match style {
+        LineStyle::Solid => String::new(),
+        LineStyle::Dash => {
+            format!(" stroke-linecap=\"round\" stroke-dasharray=\"{on_len:.3},{off_len:.3}\"")
+        }
+        LineStyle::RoundDot => {
+            // ドット間隔 (on + off) をプレビューと揃える
+            let gap = on_len + off_len - 0.01;
+            format!(" stroke-linecap=\"round\" stroke-dasharray=\"0.01,{gap:.3}\"")
+        }
+        LineStyle::SquareDot => {
+            format!(" stroke-linecap=\"butt\" stroke-dasharray=\"{thickness:.3},{off_len:.3}\"")
+        }
+    }
+}
+
 /// ---------------------------------------------
-/// 画像生成
+/// SVG ベクター出力
 /// ---------------------------------------------
-fn create_image(n_sides: usize, diameter: f64, offset_deg: f64) -> RgbaImage {
-    // diameter が大きくなっても切れないよう、画像サイズを動的に拡大
-    //   半径 = diameter/2 で ±radius 程度の範囲を使うので、
-    //   + 200ピクセル程度の余白を足しておく
-    let needed_size = (diameter as u32 + 200).max(1000); // 少なくとも 1000x1000
-    let (img_width, img_height) = (needed_size, needed_size);
-
-    // 背景色
-    let bg_color = [220u8, 220u8, 220u8, 255u8];
-    let mut img: RgbaImage = ImageBuffer::from_fn(img_width, img_height, |_x, _y| Rgba(bg_color));
+/// ピクセルに丸める前の浮動小数点座標のまま、外接円・辺・頂点・ラベルを
+/// SVG 要素として書き出す。CAD/レーザー加工用途ではラスタ画像と違って
+/// どの拡大率でも寸法が正確に保たれる。辺/外接円の線種・太さとラベルの
+/// 回転は、プレビュー/PNG/クリップボード書き出しと同じパラメータを使う。
+#[allow(clippy::too_many_arguments)]
+fn build_polygon_svg(
+    n_sides: usize,
+    diameter: f64,
+    offset_deg: f64,
+    edge_style: LineStyle,
+    edge_thickness: f32,
+    circle_style: LineStyle,
+    circle_thickness: f32,
+    rotate_labels: bool,
+) -> String {
+    let radius = diameter / 2.0;
+    let margin = 40.0;
+    let view_size = diameter + margin * 2.0;
+    let cx = view_size / 2.0;
+    let cy = view_size / 2.0;
 
     let points = generate_polygon_points(n_sides, diameter, offset_deg);
 
-    // 画像中心
-    let cx = img_width as f64 / 2.0;
-    let cy = img_height as f64 / 2.0;
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {v} {v}\" width=\"{v}\" height=\"{v}\">\n",
+        v = view_size
+    ));
 
     // 外接円
-    let radius_i = (diameter / 2.0).round() as i32;
-    draw_hollow_circle_mut(&mut img, (cx as i32, cy as i32), radius_i, Rgba([0, 0, 255, 255]));
-
-    // 多角形
-    let mut img_points = Vec::new();
-    for &(x, y, _) in &points {
-        // y軸反転はしない(後で可視化するだけ)
-        let px = (cx + x).round() as i32;
-        let py = (cy - y).round() as i32;
-        img_points.push((px, py));
+    svg.push_str(&format!(
+        "  <circle cx=\"{cx:.3}\" cy=\"{cy:.3}\" r=\"{radius:.3}\" fill=\"none\" stroke=\"blue\" stroke-width=\"{sw:.3}\"{extra} />\n",
+        sw = circle_thickness,
+        extra = svg_stroke_style(circle_style, circle_thickness as f64),
+    ));
+
+    // 多角形本体 (辺)
+    let poly_points: Vec<String> = points
+        .iter()
+        .map(|&(x, y, _)| format!("{:.3},{:.3}", cx + x, cy - y))
+        .collect();
+    svg.push_str(&format!(
+        "  <polygon points=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"{sw:.3}\"{extra} />\n",
+        poly_points.join(" "),
+        sw = edge_thickness,
+        extra = svg_stroke_style(edge_style, edge_thickness as f64),
+    ));
+
+    // 頂点マーカーと座標/角度ラベル
+    for &(x, y, angle_deg) in &points {
+        let px = cx + x;
+        let py = cy - y;
+        svg.push_str(&format!(
+            "  <circle cx=\"{px:.3}\" cy=\"{py:.3}\" r=\"3\" fill=\"red\" />\n"
+        ));
+
+        let text_x = px + 6.0;
+        let text_y = py - 6.0;
+        // ラスタ書き出し (render_export_image) と同じ符号で、外向き放射方向に
+        // 回転させる (スクリーン座標・SVG 座標ともに y は下向き)
+        let rotate_attr = if rotate_labels {
+            format!(" transform=\"rotate({:.3} {text_x:.3} {text_y:.3})\"", -angle_deg)
+        } else {
+            String::new()
+        };
+        svg.push_str(&format!(
+            "  <text x=\"{text_x:.3}\" y=\"{text_y:.3}\" font-size=\"12\" fill=\"black\"{rotate_attr}>({:.0}, {:.0}) / {:.1}°</text>\n",
+            x, y, angle_deg
+        ));
     }
-    for i in 0..n_sides {
-        let j = (i + 1) % n_sides;
-        draw_line_segment_mut(
-            &mut img,
-            (img_points[i].0 as f32, img_points[i].1 as f32),
-            (img_points[j].0 as f32, img_points[j].1 as f32),
-            Rgba([0, 0, 0, 255]),
-        );
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// ---------------------------------------------
+/// RustType を使って文字を描画する関数
+/// ---------------------------------------------
+fn draw_text(
+    img: &mut RgbaImage,
+    text: &str,
+    x: i32,
+    y: i32,
+    scale: Scale,
+    font: &Font,
+    color: [u8; 4],
+    rotation_rad: f32,
+) {
+    let v_metrics = font.v_metrics(scale);
+    let glyphs: Vec<PositionedGlyph> = font
+        .layout(text, scale, rt_point(0.0, v_metrics.ascent))
+        .collect();
+
+    for glyph in glyphs {
+        if let Some(bb) = glyph.pixel_bounding_box() {
+            glyph.draw(|gx, gy, gv| {
+                let local_x = (bb.min.x + gx as i32) as f32;
+                let local_y = (bb.min.y + gy as i32) as f32;
+                let (rx, ry) = rotate_vec(local_x, local_y, rotation_rad);
+                let px = x + rx.round() as i32;
+                let py = y + ry.round() as i32;
+
+                if px >= 0 && px < img.width() as i32 && py >= 0 && py < img.height() as i32 {
+                    let dst = img.get_pixel_mut(px as u32, py as u32);
+                    let alpha = (gv * 255.0) as u8;
+                    let inv_alpha = 255 - alpha;
+
+                    let dst_rgba = dst.0;
+                    let src_rgba = color;
+
+                    dst.0[0] = ((src_rgba[0] as u16 * alpha as u16
+                              + dst_rgba[0] as u16 * inv_alpha as u16) / 255) as u8;
+                    dst.0[1] = ((src_rgba[1] as u16 * alpha as u16
+                              + dst_rgba[1] as u16 * inv_alpha as u16) / 255) as u8;
+                    dst.0[2] = ((src_rgba[2] as u16 * alpha as u16
+                              + dst_rgba[2] as u16 * inv_alpha as u16) / 255) as u8;
+                    dst.0[3] = 255;
+                }
+            });
+        }
     }
+}
 
-    // 頂点に赤丸+テキスト
-    let font_data = include_bytes!("meiryo.ttc"); // 適宜変える
-    let font = Font::try_from_bytes(font_data as &[u8]).unwrap();
-    let scale = Scale { x: 18.0, y: 18.0 };
-    let red = Rgba([255, 0, 0, 255]);
-
-    let r = 3;
-    for (i, &(px, py)) in img_points.iter().enumerate() {
-        // 塗りつぶしの赤丸
-        for dy in -r..=r {
-            for dx in -r..=r {
-                if dx * dx + dy * dy <= r * r {
-                    let xx = px + dx;
-                    let yy = py + dy;
-                    if xx >= 0 && xx < img_width as i32 && yy >= 0 && yy < img_height as i32 {
-                        img.put_pixel(xx as u32, yy as u32, red);
-                    }
+/// HarfBuzz が返す、整形済みの1グリフ分の情報。
+struct ShapedGlyph {
+    glyph_id: u32,
+    x_advance: f32,
+    y_advance: f32,
+    x_offset: f32,
+    y_offset: f32,
+}
+
+/// HarfBuzz にラベル文字列を通し、カーニング・合字・(同梱の meiryo フォントでの)
+/// CJK クラスタリングを反映した整形済みグリフ列を得る。
+fn shape_label(font_data: &'static [u8], text: &str, font_size: f32) -> Vec<ShapedGlyph> {
+    let face = HbFace::from_bytes(font_data, 0);
+    let mut font = HbFont::new(face);
+    let units = (font_size * 64.0) as i32;
+    font.set_scale(units, units);
+
+    let buffer = UnicodeBuffer::new().add_str(text);
+    let output = shape(&font, buffer, &[]);
+
+    output
+        .get_glyph_positions()
+        .iter()
+        .zip(output.get_glyph_infos())
+        .map(|(pos, info)| ShapedGlyph {
+            glyph_id: info.codepoint,
+            x_advance: pos.x_advance as f32 / 64.0,
+            y_advance: pos.y_advance as f32 / 64.0,
+            x_offset: pos.x_offset as f32 / 64.0,
+            y_offset: pos.y_offset as f32 / 64.0,
+        })
+        .collect()
+}
+
+/// ベクトル `(x, y)` を原点中心に `angle_rad` だけ回転させる。
+fn rotate_vec(x: f32, y: f32, angle_rad: f32) -> (f32, f32) {
+    let (sin, cos) = angle_rad.sin_cos();
+    (x * cos - y * sin, x * sin + y * cos)
+}
+
+/// HarfBuzz で整形したグリフ列を rusttype でラスタライズし、`(x, y)` を
+/// 中心として `rotation_rad` だけ回転させて描画する。頂点の外向き放射方向に
+/// ラベルを沿わせたい場合に使う。
+#[allow(clippy::too_many_arguments)]
+fn draw_text_shaped(
+    img: &mut RgbaImage,
+    font_data: &'static [u8],
+    text: &str,
+    x: i32,
+    y: i32,
+    scale: Scale,
+    font: &Font,
+    color: [u8; 4],
+    rotation_rad: f32,
+) {
+    let v_metrics = font.v_metrics(scale);
+    let shaped = shape_label(font_data, text, scale.x);
+
+    let mut pen_x = 0.0_f32;
+    let mut pen_y = 0.0_f32;
+    for glyph_info in &shaped {
+        let glyph = font
+            .glyph(GlyphId(glyph_info.glyph_id as u16))
+            .scaled(scale)
+            .positioned(rt_point(
+                pen_x + glyph_info.x_offset,
+                v_metrics.ascent + pen_y - glyph_info.y_offset,
+            ));
+
+        if let Some(bb) = glyph.pixel_bounding_box() {
+            glyph.draw(|gx, gy, gv| {
+                let local_x = (bb.min.x + gx as i32) as f32;
+                let local_y = (bb.min.y + gy as i32) as f32;
+                let (rx, ry) = rotate_vec(local_x, local_y, rotation_rad);
+                let px = x + rx.round() as i32;
+                let py = y + ry.round() as i32;
+
+                if px >= 0 && px < img.width() as i32 && py >= 0 && py < img.height() as i32 {
+                    let dst = img.get_pixel_mut(px as u32, py as u32);
+                    let alpha = (gv * 255.0) as u8;
+                    let inv_alpha = 255 - alpha;
+
+                    let dst_rgba = dst.0;
+                    let src_rgba = color;
+
+                    dst.0[0] = ((src_rgba[0] as u16 * alpha as u16
+                              + dst_rgba[0] as u16 * inv_alpha as u16) / 255) as u8;
+                    dst.0[1] = ((src_rgba[1] as u16 * alpha as u16
+                              + dst_rgba[1] as u16 * inv_alpha as u16) / 255) as u8;
+                    dst.0[2] = ((src_rgba[2] as u16 * alpha as u16
+                              + dst_rgba[2] as u16 * inv_alpha as u16) / 255) as u8;
+                    dst.0[3] = 255;
                 }
-            }
+            });
         }
-        // 座標と角度ラベル
-        let (orig_x, orig_y, angle_deg) = points[i];
+
+        pen_x += glyph_info.x_advance;
+        pen_y += glyph_info.y_advance;
+    }
+}
+
+/// ---------------------------------------------
+/// ラスタ画像書き出し (クリップボード / PNG 保存用)
+/// ---------------------------------------------
+/// プレビューはベクターメッシュで Painter 描画するが、クリップボードへの
+/// コピーや PNG ファイル保存には実ピクセルの RGBA バッファが要る。
+/// 外接円・辺・頂点ドットは Generate ボタンと同じ `tessellate_polygon_mesh`
+/// の出力をそのままラスタライズする。書き出し時点のパラメータでテッセレーションし直す
+/// ため、Generate 後にパラメータを変えていなければ線種・太さはプレビューと一致する。
+#[allow(clippy::too_many_arguments)]
+fn render_export_image(
+    n_sides: usize,
+    diameter: f64,
+    offset_deg: f64,
+    edge_style: LineStyle,
+    edge_thickness: f32,
+    circle_style: LineStyle,
+    circle_thickness: f32,
+    use_harfbuzz_shaping: bool,
+    rotate_labels: bool,
+) -> RgbaImage {
+    let needed_size = (diameter as u32 + 200).max(1000); // 少なくとも 1000x1000
+    let (img_width, img_height) = (needed_size, needed_size);
+
+    let bg_color = [220u8, 220u8, 220u8, 255u8];
+    let mut img: RgbaImage = ImageBuffer::from_fn(img_width, img_height, |_x, _y| Rgba(bg_color));
+
+    let cx = img_width as f32 / 2.0;
+    let cy = img_height as f32 / 2.0;
+
+    let mesh = tessellate_polygon_mesh(
+        n_sides,
+        diameter,
+        offset_deg,
+        edge_style,
+        edge_thickness,
+        circle_style,
+        circle_thickness,
+        0.2,
+    );
+    rasterize_mesh(&mut img, &mesh, cx, cy);
+
+    let points = generate_polygon_points(n_sides, diameter, offset_deg);
+    let font_data = include_bytes!("meiryo.ttc");
+    let font = Font::try_from_bytes(font_data as &[u8]).unwrap();
+    let scale = Scale { x: 18.0, y: 18.0 };
+
+    for &(orig_x, orig_y, angle_deg) in &points {
+        let px = (cx as f64 + orig_x).round() as i32;
+        let py = (cy as f64 - orig_y).round() as i32;
         let text_str = format!("({:.0}, {:.0}) / {:.1}°", orig_x, orig_y, angle_deg);
-        draw_text(&mut img, &text_str, px + 6, py - 12, scale, &font, [0, 0, 0, 255]);
+
+        // 外向き放射方向の角度 (スクリーン座標は y が下向きなので符号を反転する)
+        let rotation_rad = if rotate_labels {
+            -(angle_deg.to_radians() as f32)
+        } else {
+            0.0
+        };
+
+        if use_harfbuzz_shaping {
+            draw_text_shaped(
+                &mut img, font_data, &text_str, px + 6, py - 12, scale, &font, [0, 0, 0, 255],
+                rotation_rad,
+            );
+        } else {
+            draw_text(
+                &mut img, &text_str, px + 6, py - 12, scale, &font, [0, 0, 0, 255], rotation_rad,
+            );
+        }
     }
 
     img